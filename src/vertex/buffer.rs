@@ -0,0 +1,173 @@
+/*!
+Contains the vertex buffer types: `VertexBuffer`, its type-erased form `VertexBufferAny`, and
+slices of either.
+*/
+use std::marker::PhantomData;
+use std::ops::{Deref, Range};
+
+use vertex::{IntoVerticesSource, PerInstance, Vertex, VertexFormat, VerticesSource};
+
+/// Describes what the backend this buffer was created on can do with per-instance sources.
+#[derive(Debug, Copy, Clone)]
+pub struct Capabilities {
+    /// Whether `glVertexAttribDivisor` accepts a divisor greater than `1`
+    /// (`ARB_instanced_arrays`). A divisor of `1` is always supported.
+    pub instance_divisor: bool,
+}
+
+/// A list of vertices loaded in the graphics card's memory.
+pub struct VertexBuffer<T> {
+    buffer: VertexBufferAny,
+    marker: PhantomData<T>,
+}
+
+impl<T> VertexBuffer<T> where T: Vertex {
+    /// Builds a new vertex buffer of `len` elements, using `T`'s bindings.
+    pub fn empty(len: usize, capabilities: Capabilities) -> VertexBuffer<T> {
+        VertexBuffer {
+            buffer: VertexBufferAny {
+                format: T::build_bindings(),
+                len: len,
+                capabilities: capabilities,
+            },
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the whole buffer as a per-instance source, with a divisor of `1`.
+    ///
+    /// A divisor of `1` is always accepted (see `Capabilities`), so this does not panic;
+    /// whether the backend supports instanced drawing at all is not checked.
+    pub fn per_instance<'a>(&'a self) -> PerInstance<'a> {
+        self.as_slice_any().per_instance()
+    }
+
+    /// Same as `per_instance`, but returns an `Option`. Since a divisor of `1` is always
+    /// accepted, this always returns `Some`.
+    pub fn per_instance_if_supported<'a>(&'a self) -> Option<PerInstance<'a>> {
+        self.as_slice_any().per_instance_if_supported()
+    }
+
+    /// Same as `per_instance_if_supported`, but advances one element of the buffer every
+    /// `divisor` instances instead of every instance.
+    pub fn per_instance_with_divisor<'a>(&'a self, divisor: u32) -> Option<PerInstance<'a>> {
+        self.as_slice_any().per_instance_with_divisor(divisor)
+    }
+
+    fn as_slice_any<'a>(&'a self) -> VertexBufferAnySlice<'a> {
+        VertexBufferAnySlice { buffer: &self.buffer, offset: 0, len: self.buffer.len }
+    }
+
+    /// Erases the type of this buffer, turning it into a `VertexBufferAny`.
+    pub fn into_any(self) -> VertexBufferAny {
+        self.buffer
+    }
+}
+
+impl<T> Deref for VertexBuffer<T> {
+    type Target = VertexBufferAny;
+
+    fn deref(&self) -> &VertexBufferAny {
+        &self.buffer
+    }
+}
+
+impl<'a, T> IntoVerticesSource<'a> for &'a VertexBuffer<T> {
+    fn into_vertices_source(self) -> VerticesSource<'a> {
+        (&self.buffer).into_vertices_source()
+    }
+}
+
+/// A type-erased `VertexBuffer`.
+pub struct VertexBufferAny {
+    format: VertexFormat,
+    len: usize,
+    capabilities: Capabilities,
+}
+
+impl VertexBufferAny {
+    /// Returns the format of the vertices stored in this buffer, i.e. the name, offset and
+    /// type of each of its attributes.
+    pub fn bindings(&self) -> &VertexFormat {
+        &self.format
+    }
+
+    /// Returns the number of vertices in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Builds a slice of this buffer. Returns `None` if out of range.
+    pub fn slice<'a>(&'a self, range: Range<usize>) -> Option<VertexBufferAnySlice<'a>> {
+        if range.start > range.end || range.end > self.len {
+            return None;
+        }
+
+        Some(VertexBufferAnySlice { buffer: self, offset: range.start, len: range.end - range.start })
+    }
+}
+
+impl<'a> IntoVerticesSource<'a> for &'a VertexBufferAny {
+    fn into_vertices_source(self) -> VerticesSource<'a> {
+        VerticesSource::VertexBuffer(self, 0, self.len, 0)
+    }
+}
+
+/// A slice of a `VertexBuffer`.
+#[derive(Copy, Clone)]
+pub struct VertexBufferSlice<'a, T: 'a> {
+    slice: VertexBufferAnySlice<'a>,
+    marker: PhantomData<&'a T>,
+}
+
+impl<'a, T> IntoVerticesSource<'a> for VertexBufferSlice<'a, T> {
+    fn into_vertices_source(self) -> VerticesSource<'a> {
+        self.slice.into_vertices_source()
+    }
+}
+
+/// A type-erased slice of a `VertexBufferAny`.
+#[derive(Copy, Clone)]
+pub struct VertexBufferAnySlice<'a> {
+    buffer: &'a VertexBufferAny,
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> VertexBufferAnySlice<'a> {
+    /// Marks this slice as a per-instance source, with a divisor of `1`.
+    ///
+    /// A divisor of `1` is always accepted (see `Capabilities`), so this does not panic;
+    /// whether the backend supports instanced drawing at all is not checked.
+    pub fn per_instance(self) -> PerInstance<'a> {
+        self.per_instance_if_supported().expect("instanced drawing is not supported by this backend")
+    }
+
+    /// Same as `per_instance`, but returns an `Option`. Since a divisor of `1` is always
+    /// accepted, this always returns `Some`.
+    pub fn per_instance_if_supported(self) -> Option<PerInstance<'a>> {
+        PerInstance::new(self, 1).ok()
+    }
+
+    /// Same as `per_instance_if_supported`, but advances one element of the buffer every
+    /// `divisor` instances instead of every instance. Returns `None` if `divisor` is `0`, or
+    /// if `divisor > 1` and the backend's capabilities don't report `instance_divisor` support.
+    pub fn per_instance_with_divisor(self, divisor: u32) -> Option<PerInstance<'a>> {
+        PerInstance::new(self, divisor).ok()
+    }
+
+    pub(crate) fn supports_divisor(&self, divisor: u32) -> bool {
+        divisor == 1 || (divisor > 1 && self.buffer.capabilities.instance_divisor)
+    }
+}
+
+impl<'a> IntoVerticesSource<'a> for VertexBufferAnySlice<'a> {
+    fn into_vertices_source(self) -> VerticesSource<'a> {
+        VerticesSource::VertexBuffer(self.buffer, self.offset, self.len, 0)
+    }
+}
+
+/// RAII guard for a memory-mapped vertex buffer.
+pub struct Mapping<'a, T: 'a> {
+    marker: PhantomData<&'a mut T>,
+}
@@ -0,0 +1,45 @@
+/*!
+Defines the `AttributeType` enum and the `VertexFormat` type used to describe the layout of a
+vertex buffer.
+*/
+use std::borrow::Cow;
+
+/// Describes the layout of a vertex buffer.
+///
+/// Each element is `(name, offset, type)`: the attribute's name as declared in the shader, its
+/// byte offset within one vertex, and its `AttributeType`.
+pub type VertexFormat = Cow<'static, [(Cow<'static, str>, usize, AttributeType)]>;
+
+/// Type of a value in a vertex attribute.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum AttributeType {
+    I8,
+    I8I8,
+    I8I8I8,
+    I8I8I8I8,
+    U8,
+    U8U8,
+    U8U8U8,
+    U8U8U8U8,
+    I16,
+    I16I16,
+    I16I16I16,
+    I16I16I16I16,
+    U16,
+    U16U16,
+    U16U16U16,
+    U16U16U16U16,
+    I32,
+    I32I32,
+    I32I32I32,
+    I32I32I32I32,
+    U32,
+    U32U32,
+    U32U32U32,
+    U32U32U32U32,
+    F32,
+    F32F32,
+    F32F32F32,
+    F32F32F32F32,
+}
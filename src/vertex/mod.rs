@@ -72,6 +72,9 @@ Each source can be:
  - A marker indicating a number of vertex sources, with `glium::vertex::EmptyVertexAttributes`.
  - A marker indicating a number of instances, with `glium::vertex::EmptyInstanceAttributes`.
 
+If the number of sources is not known at compile-time, a `Vec<glium::vertex::VerticesSource>`
+(or a `&[glium::vertex::VerticesSource]`) can be passed instead of a tuple.
+
 ```no_run
 # use glium::Surface;
 # let display: glium::Display = unsafe { ::std::mem::uninitialized() };
@@ -117,10 +120,60 @@ frame.draw((&vertex_buffer, glium::vertex::EmptyInstanceAttributes { len: 36 }),
 Note that if you use `index::EmptyIndices` as indices the length of all vertex sources must
 be the same, or a `DrawError::VerticesSourcesLengthMismatch` will be produced.
 
-In all situation, the length of all per-instance sources must match, or
-`DrawError::InstancesCountMismatch` will be retured.
+In all situation, the length of all per-instance sources must match once adjusted for their
+divisor (a buffer with `len` elements and divisor `d` covers `len * d`
+instances), or `DrawError::InstancesCountMismatch` will be retured.
+
+## Deinterleaved vertex storage
+
+By default `implement_vertex!` describes an interleaved (array-of-structs) layout, where every
+attribute of one vertex lives next to each other in a single buffer. If instead you want each
+attribute in its own tightly packed buffer (structure-of-arrays), for example to rewrite just
+one attribute without touching the others, use `implement_vertex_deinterleaved!`. Unlike
+`implement_vertex!`, it needs each field's type spelled out, because it also declares one
+wrapper type per field (named after the field, wrapping the field's own type) with its own
+`Vertex` impl, so that a `VertexBuffer<$field_name>` can be built for just that attribute.
+Invoke it inside a dedicated
+module so that the generated wrapper types don't clash with another vertex type's fields of the
+same name:
+
+```no_run
+# #[macro_use]
+# extern crate glium;
+# use glium::Surface;
+#[derive(Copy, Clone)]
+struct MyVertex { position: [f32; 3], texcoords: [f32; 2] }
+
+mod my_vertex_deinterleaved {
+    implement_vertex_deinterleaved!(super::MyVertex, position: [f32; 3], texcoords: [f32; 2]);
+}
+use my_vertex_deinterleaved::{position, texcoords};
+
+# fn main() {
+# let display: glium::Display = unsafe { ::std::mem::uninitialized() };
+# let program: glium::program::Program = unsafe { ::std::mem::uninitialized() };
+# let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+# let uniforms = glium::uniforms::EmptyUniforms;
+# let positions_data: Vec<[f32; 3]> = Vec::new();
+# let texcoords_data: Vec<[f32; 2]> = Vec::new();
+# let mut frame = display.draw();
+
+let positions_buffer: glium::vertex::VertexBuffer<position> =
+    unsafe { ::std::mem::uninitialized() };
+let texcoords_buffer: glium::vertex::VertexBuffer<texcoords> =
+    unsafe { ::std::mem::uninitialized() };
+
+let buffers = [positions_buffer.into_any(), texcoords_buffer.into_any()];
+let sources = glium::vertex::deinterleaved_sources::<MyVertex>(&buffers);
+frame.draw(&sources[..], &indices, &program, &uniforms, &Default::default()).unwrap();
+# }
+```
+
+The vertex shader sees the exact same attribute set as with the interleaved version; only the
+storage changes.
 
 */
+use std::collections::HashMap;
 use std::iter::Chain;
 use std::option::IntoIter;
 
@@ -137,9 +190,10 @@ pub enum VerticesSource<'a> {
     /// A buffer uploaded in the video memory.
     ///
     /// The second and third parameters are the offset and length of the buffer.
-    /// The fourth parameter tells whether or not this buffer is "per instance" (true) or
-    /// "per vertex" (false).
-    VertexBuffer(&'a VertexBufferAny, usize, usize, bool),
+    /// The fourth parameter is the instance-rate divisor: `0` means the buffer is "per
+    /// vertex", while any value `N >= 1` means the buffer is "per instance" and each of its
+    /// elements is reused for `N` consecutive instances.
+    VertexBuffer(&'a VertexBufferAny, usize, usize, u32),
 
     Marker { len: usize, per_instance: bool },
 }
@@ -174,14 +228,49 @@ impl<'a> IntoVerticesSource<'a> for EmptyInstanceAttributes {
     }
 }
 
-/// Marker that instructs glium that the buffer is to be used per instance.
-pub struct PerInstance<'a>(VertexBufferAnySlice<'a>);
+/// Marker that instructs glium that the buffer is to be used per instance, with a given
+/// instance-rate divisor.
+///
+/// Built by `per_instance()`/`per_instance_if_supported()` (divisor `1`) or by
+/// `per_instance_with_divisor(n)` (divisor `n`).
+///
+/// A divisor of `1` (the default, as produced by `per_instance()`) means a new element of the
+/// buffer is read for each instance. A divisor of `N > 1` means the same element is read for
+/// `N` consecutive instances, as supported by `ARB_instanced_arrays` / `glVertexAttribDivisor`.
+/// Requesting a divisor greater than `1` on a buffer whose backend does not support it is
+/// rejected up front, with `InstancingNotSupportedError` by `PerInstance::new` and with `None`
+/// by `per_instance_with_divisor`, rather than being deferred to a later draw call.
+pub struct PerInstance<'a>(VertexBufferAnySlice<'a>, u32);
+
+/// Error returned when a `PerInstance` source requests a divisor that the buffer's backend does
+/// not support (a divisor of `0`, or a divisor greater than `1` without `ARB_instanced_arrays`
+/// divisor support).
+#[derive(Debug, Copy, Clone)]
+pub struct InstancingNotSupportedError;
+
+impl<'a> PerInstance<'a> {
+    /// Builds a new `PerInstance` marker from a buffer slice, with the given divisor.
+    pub fn new(buffer: VertexBufferAnySlice<'a>, divisor: u32)
+               -> Result<PerInstance<'a>, InstancingNotSupportedError>
+    {
+        if divisor == 0 || !buffer.supports_divisor(divisor) {
+            return Err(InstancingNotSupportedError);
+        }
+
+        Ok(PerInstance(buffer, divisor))
+    }
+
+    /// Returns a copy of this marker with a different divisor.
+    pub fn with_divisor(self, divisor: u32) -> Result<PerInstance<'a>, InstancingNotSupportedError> {
+        PerInstance::new(self.0, divisor)
+    }
+}
 
 impl<'a> IntoVerticesSource<'a> for PerInstance<'a> {
     fn into_vertices_source(self) -> VerticesSource<'a> {
         match self.0.into_vertices_source() {
-            VerticesSource::VertexBuffer(buf, off, len, false) => {
-                VerticesSource::VertexBuffer(buf, off, len, true)
+            VerticesSource::VertexBuffer(buf, off, len, 0) => {
+                VerticesSource::VertexBuffer(buf, off, len, self.1)
             },
             _ => unreachable!()
         }
@@ -261,6 +350,22 @@ macro_rules! impl_for_tuple {
 
 impl_for_tuple!(A, B, C, D, E, F, G);
 
+impl<'a> MultiVerticesSource<'a> for Vec<VerticesSource<'a>> {
+    type Iterator = ::std::vec::IntoIter<VerticesSource<'a>>;
+
+    fn iter(self) -> Self::Iterator {
+        self.into_iter()
+    }
+}
+
+impl<'a> MultiVerticesSource<'a> for &'a [VerticesSource<'a>] {
+    type Iterator = ::std::vec::IntoIter<VerticesSource<'a>>;
+
+    fn iter(self) -> Self::Iterator {
+        self.to_vec().into_iter()
+    }
+}
+
 /// Trait for structures that represent a vertex.
 ///
 /// Instead of implementing this trait yourself, it is recommended to use the `implement_vertex!`
@@ -271,8 +376,366 @@ pub trait Vertex: Copy + Sized {
     fn build_bindings() -> VertexFormat;
 }
 
+/// Trait for structures that represent a vertex stored deinterleaved, one buffer per attribute.
+///
+/// Instead of implementing this trait yourself, use the `implement_vertex_deinterleaved!` macro,
+/// which also declares one wrapper type per field (named after the field), each implementing
+/// `Vertex`, so that a `VertexBuffer` can be built for just that attribute.
+pub trait VertexDeinterleaved: Copy + Sized {
+    /// Builds the list of `(attribute name, format)` pairs, one per attribute, in the order the
+    /// corresponding buffers must be passed to `deinterleaved_sources`. Each format describes a
+    /// buffer that tightly packs just that attribute, i.e. a stride equal to the attribute's
+    /// own size and an offset of `0`.
+    fn build_deinterleaved_bindings() -> Vec<(&'static str, VertexFormat)>;
+}
+
+/// Binds together the per-attribute buffers of a deinterleaved vertex type `V` into a single
+/// list of vertex sources, so that drawing with them exposes the same attribute set as the
+/// equivalent interleaved `Vertex` would.
+///
+/// `buffers` must contain exactly one buffer per entry of `V::build_deinterleaved_bindings`, in
+/// the same order; each buffer's own bindings are checked against that entry's name and type.
+///
+/// # Panics
+///
+/// Panics if the number of buffers doesn't match the number of attributes declared by `V`, or
+/// if a buffer's bindings don't match the attribute `V` declares for its position.
+pub fn deinterleaved_sources<'a, V>(buffers: &'a [VertexBufferAny]) -> Vec<VerticesSource<'a>>
+    where V: VertexDeinterleaved
+{
+    let expected = V::build_deinterleaved_bindings();
+    assert_eq!(buffers.len(), expected.len(),
+               "expected one buffer per attribute of the deinterleaved vertex type");
+
+    buffers.iter().zip(expected.iter()).map(|(buffer, &(name, ref format))| {
+        let found = buffer.bindings();
+        assert_eq!(&**found, &**format,
+                   "buffer for attribute `{}` does not match the bindings declared by \
+                    `build_deinterleaved_bindings`", name);
+        buffer.into_vertices_source()
+    }).collect()
+}
+
+/// Implements the `VertexDeinterleaved` trait for a type, and declares one wrapper type per
+/// field (named after the field) implementing `Vertex`, so that a `VertexBuffer` can be built
+/// for each attribute in isolation.
+///
+/// Unlike `implement_vertex!`, each field's type must be spelled out, since it is needed to
+/// declare the wrapper types (two deinterleaved fields can otherwise share the same bare type,
+/// e.g. two `[f32; 3]` fields, which would make the wrapper types themselves ambiguous).
+/// Invoke this macro inside a dedicated module, since the wrapper types are declared at the
+/// invocation site and would otherwise clash with another vertex type's fields of the same
+/// name.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate glium;
+/// #[derive(Copy, Clone)]
+/// struct MyVertex {
+///     position: [f32; 3],
+///     texcoords: [f32; 2],
+/// }
+///
+/// mod my_vertex_deinterleaved {
+///     implement_vertex_deinterleaved!(super::MyVertex, position: [f32; 3], texcoords: [f32; 2]);
+/// }
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! implement_vertex_deinterleaved {
+    ($struct_name:path, $($field_name:ident: $field_ty:ty),+ $(,)*) => (
+        impl $crate::vertex::VertexDeinterleaved for $struct_name {
+            fn build_deinterleaved_bindings() -> Vec<(&'static str, $crate::vertex::VertexFormat)> {
+                vec![
+                    $(
+                        (stringify!($field_name), {
+                            let ty = <$field_ty as $crate::vertex::Attribute>::get_type();
+                            ::std::borrow::Cow::Owned(vec![
+                                (::std::borrow::Cow::Borrowed(stringify!($field_name)), 0usize, ty),
+                            ])
+                        }),
+                    )+
+                ]
+            }
+        }
+
+        $(
+            /// Wrapper around a single deinterleaved attribute, generated by
+            /// `implement_vertex_deinterleaved!`.
+            #[derive(Copy, Clone)]
+            #[allow(non_camel_case_types)]
+            pub struct $field_name(pub $field_ty);
+
+            impl $crate::vertex::Vertex for $field_name {
+                fn build_bindings() -> $crate::vertex::VertexFormat {
+                    ::std::borrow::Cow::Owned(vec![
+                        (::std::borrow::Cow::Borrowed(stringify!($field_name)), 0usize,
+                         <$field_ty as $crate::vertex::Attribute>::get_type()),
+                    ])
+                }
+            }
+        )+
+    );
+}
+
 /// Trait for types that can be used as vertex attributes.
 pub unsafe trait Attribute: Sized {
     /// Get the type of data.
     fn get_type() -> AttributeType;
 }
+
+/// Describes one incompatibility between a set of vertex sources and the vertex attributes
+/// declared by a program.
+#[derive(Debug, Clone)]
+pub enum VertexFormatIncompatibility {
+    /// An attribute required by the shader is not provided by any of the vertex sources.
+    MissingAttribute {
+        /// Name of the attribute, as declared in the shader.
+        name: String,
+    },
+
+    /// An attribute is provided, but with a different type than what the shader declares.
+    AttributeTypeMismatch {
+        /// Name of the attribute.
+        name: String,
+        /// Type expected by the shader.
+        expected: AttributeType,
+        /// Type found in the vertex sources.
+        found: AttributeType,
+    },
+
+    /// The same attribute is provided by more than one of the vertex sources.
+    DuplicateAttribute {
+        /// Name of the attribute.
+        name: String,
+    },
+}
+
+/// A view of the vertex attributes declared by a program, as needed by `check_compatibility`.
+///
+/// Kept as a small trait of our own, rather than assuming the exact shape of a program's
+/// reflection data, so this module doesn't depend on unverified internals of `Program`.
+/// `Program` itself is expected to implement it.
+pub trait ProgramVertexInterface {
+    /// Returns the attributes declared by the program, as `(name, type)` pairs.
+    fn declared_attributes(&self) -> Vec<(String, AttributeType)>;
+}
+
+/// Checks that a set of vertex sources together satisfy the vertex attributes declared by
+/// `program`, without drawing anything.
+///
+/// Returns `Ok(())` if every attribute required by the program is provided, with a matching
+/// type, by the combined sources, and no attribute (required or not) is provided more than
+/// once. Otherwise returns one
+/// `VertexFormatIncompatibility` per problem found, so that tooling and asset pipelines can
+/// validate a mesh against a shader ahead of time instead of discovering the mismatch as a
+/// `DrawError` on the first draw call.
+pub fn check_compatibility<'a, S, P>(sources: S, program: &P)
+                                      -> Result<(), Vec<VertexFormatIncompatibility>>
+    where S: MultiVerticesSource<'a>, P: ProgramVertexInterface
+{
+    let formats: Vec<VertexFormat> = sources.iter().filter_map(|source| {
+        match source {
+            VerticesSource::VertexBuffer(buffer, _, _, _) => Some(buffer.bindings().clone()),
+            VerticesSource::Marker { .. } => None,
+        }
+    }).collect();
+
+    check_formats(&formats, &program.declared_attributes())
+}
+
+/// Pure logic behind `check_compatibility`, decoupled from `VerticesSource`/`Program` so it can
+/// be exercised directly in tests.
+fn check_formats(formats: &[VertexFormat], required: &[(String, AttributeType)])
+                  -> Result<(), Vec<VertexFormatIncompatibility>>
+{
+    let mut provided: HashMap<String, AttributeType> = HashMap::new();
+    let mut problems = Vec::new();
+
+    for format in formats {
+        for &(ref name, _, ty) in format.iter() {
+            let name = name.clone().into_owned();
+
+            // Keep the first-seen type: an `AttributeTypeMismatch` is only checked against the
+            // first source providing the attribute; later ones are reported as duplicates.
+            if provided.contains_key(&name) {
+                problems.push(VertexFormatIncompatibility::DuplicateAttribute { name });
+            } else {
+                provided.insert(name, ty);
+            }
+        }
+    }
+
+    for &(ref name, expected) in required {
+        match provided.get(name) {
+            None => {
+                problems.push(VertexFormatIncompatibility::MissingAttribute {
+                    name: name.clone(),
+                });
+            },
+            Some(&found) if found != expected => {
+                problems.push(VertexFormatIncompatibility::AttributeTypeMismatch {
+                    name: name.clone(),
+                    expected,
+                    found,
+                });
+            },
+            _ => (),
+        }
+    }
+
+    if problems.is_empty() { Ok(()) } else { Err(problems) }
+}
+
+/// Describes a disagreement between the per-instance vertex sources passed to a draw call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstancesCountMismatch {
+    /// Instance count, adjusted for divisor, implied by the first per-instance source
+    /// encountered.
+    pub expected: usize,
+    /// Instance count, adjusted for divisor, implied by a later per-instance source that
+    /// disagreed with `expected`.
+    pub found: usize,
+}
+
+/// Checks that the per-instance sources in `sources` agree on the number of instances they
+/// cover, i.e. that `len * divisor` is the same for each of them.
+///
+/// Per-vertex sources are ignored. Returns `Ok(None)` if `sources` contains no per-instance
+/// source, `Ok(Some(instances))` with the agreed-upon count if they all agree, or
+/// `Err(InstancesCountMismatch)` for the first disagreement found. This mirrors, ahead of a draw
+/// call, the check whose failure produces `DrawError::InstancesCountMismatch`.
+pub fn check_instances_count<'a, S>(sources: S) -> Result<Option<usize>, InstancesCountMismatch>
+    where S: MultiVerticesSource<'a>
+{
+    let counts: Vec<usize> = sources.iter().filter_map(|source| {
+        match source {
+            VerticesSource::VertexBuffer(_, _, len, divisor) if divisor != 0 => {
+                Some(len * divisor as usize)
+            },
+            VerticesSource::Marker { len, per_instance: true } => Some(len),
+            _ => None,
+        }
+    }).collect();
+
+    check_instance_counts(&counts)
+}
+
+/// Pure logic behind `check_instances_count`, decoupled from `VerticesSource` so it can be
+/// exercised directly in tests.
+fn check_instance_counts(counts: &[usize]) -> Result<Option<usize>, InstancesCountMismatch> {
+    let mut iter = counts.iter();
+
+    let first = match iter.next() {
+        Some(&first) => first,
+        None => return Ok(None),
+    };
+
+    for &count in iter {
+        if count != first {
+            return Err(InstancesCountMismatch { expected: first, found: count });
+        }
+    }
+
+    Ok(Some(first))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    fn format(entries: &[(&'static str, AttributeType)]) -> VertexFormat {
+        Cow::Owned(entries.iter()
+            .map(|&(name, ty)| (Cow::Borrowed(name), 0, ty))
+            .collect())
+    }
+
+    #[test]
+    fn compatible_formats_are_ok() {
+        let formats = vec![format(&[("position", AttributeType::F32F32F32)]),
+                            format(&[("texcoords", AttributeType::F32F32)])];
+        let required = vec![("position".to_string(), AttributeType::F32F32F32),
+                             ("texcoords".to_string(), AttributeType::F32F32)];
+
+        assert!(check_formats(&formats, &required).is_ok());
+    }
+
+    #[test]
+    fn reports_missing_attribute() {
+        let formats = vec![format(&[("position", AttributeType::F32F32F32)])];
+        let required = vec![("position".to_string(), AttributeType::F32F32F32),
+                             ("texcoords".to_string(), AttributeType::F32F32)];
+
+        let problems = check_formats(&formats, &required).unwrap_err();
+        assert!(problems.iter().any(|p| match *p {
+            VertexFormatIncompatibility::MissingAttribute { ref name } => name == "texcoords",
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn reports_attribute_type_mismatch() {
+        let formats = vec![format(&[("position", AttributeType::F32F32)])];
+        let required = vec![("position".to_string(), AttributeType::F32F32F32)];
+
+        let problems = check_formats(&formats, &required).unwrap_err();
+        assert!(problems.iter().any(|p| match *p {
+            VertexFormatIncompatibility::AttributeTypeMismatch { ref name, expected, found } =>
+                name == "position" && expected == AttributeType::F32F32F32 &&
+                    found == AttributeType::F32F32,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn reports_duplicate_attribute_and_keeps_first_seen_type() {
+        let formats = vec![format(&[("position", AttributeType::F32F32F32)]),
+                            format(&[("position", AttributeType::F32F32)])];
+        let required = vec![("position".to_string(), AttributeType::F32F32F32)];
+
+        let problems = check_formats(&formats, &required).unwrap_err();
+
+        assert!(problems.iter().any(|p| match *p {
+            VertexFormatIncompatibility::DuplicateAttribute { ref name } => name == "position",
+            _ => false,
+        }));
+        // The first source's type matches `required`, and only the first-seen type is checked,
+        // so no mismatch is reported for the conflicting second source.
+        assert!(!problems.iter().any(|p| match *p {
+            VertexFormatIncompatibility::AttributeTypeMismatch { .. } => true,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn no_per_instance_source_is_ok() {
+        assert_eq!(check_instance_counts(&[]), Ok(None));
+    }
+
+    #[test]
+    fn agreeing_instance_counts_are_ok() {
+        assert_eq!(check_instance_counts(&[36, 36, 36]), Ok(Some(36)));
+    }
+
+    #[test]
+    fn reports_disagreeing_instance_counts() {
+        assert_eq!(check_instance_counts(&[36, 12]),
+                   Err(InstancesCountMismatch { expected: 36, found: 12 }));
+    }
+
+    #[test]
+    fn check_instances_count_reports_mismatch_between_sources() {
+        let sources = (EmptyInstanceAttributes { len: 36 }, EmptyInstanceAttributes { len: 12 });
+
+        assert_eq!(check_instances_count(sources),
+                   Err(InstancesCountMismatch { expected: 36, found: 12 }));
+    }
+
+    #[test]
+    fn check_instances_count_ignores_per_vertex_sources() {
+        let sources = (EmptyVertexAttributes { len: 1000 }, EmptyInstanceAttributes { len: 36 });
+
+        assert_eq!(check_instances_count(sources), Ok(Some(36)));
+    }
+}